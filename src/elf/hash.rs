@@ -0,0 +1,344 @@
+//! Hash tables for O(1)-ish ELF symbol-by-name lookup: the classic SysV `.hash` section, and
+//! the GNU `.gnu.hash` extension most modern dynamic linkers prefer. Both sit alongside the
+//! dynamic symbol table and its [`Strtab`](../../strtab/struct.Strtab.html), and let callers
+//! avoid a linear scan of every `Sym` when resolving a symbol by name.
+
+use scroll::{self, Pread};
+use elf::sym::Sym;
+use strtab::Strtab;
+use error;
+
+/// A SysV `.hash` section: `nbucket`/`nchain` followed by a `bucket` and `chain` array of `u32`s.
+pub struct SysvHash<'a> {
+    bytes: &'a [u8],
+    nbucket: usize,
+    nchain: usize,
+    bucket_offset: usize,
+    chain_offset: usize,
+}
+
+impl<'a> SysvHash<'a> {
+    /// Parses a SysV `.hash` section from `bytes`
+    pub fn parse(bytes: &'a [u8]) -> error::Result<Self> {
+        let nbucket: u32 = bytes.pread_with(0, scroll::LE)?;
+        let nchain: u32 = bytes.pread_with(4, scroll::LE)?;
+        let bucket_offset = 8;
+        let chain_offset = bucket_offset + nbucket as usize * 4;
+        Ok(SysvHash {
+            bytes: bytes,
+            nbucket: nbucket as usize,
+            nchain: nchain as usize,
+            bucket_offset: bucket_offset,
+            chain_offset: chain_offset,
+        })
+    }
+
+    /// The classic SysV ELF string hash
+    pub fn hash(name: &str) -> u32 {
+        let mut h: u32 = 0;
+        for c in name.bytes() {
+            h = h.wrapping_shl(4).wrapping_add(c as u32);
+            let g = h & 0xf000_0000;
+            if g != 0 {
+                h ^= g >> 24;
+            }
+            h &= !g;
+        }
+        h
+    }
+
+    fn bucket(&self, idx: usize) -> error::Result<u32> {
+        self.bytes.pread_with(self.bucket_offset + idx * 4, scroll::LE).map_err(error::Error::from)
+    }
+
+    fn chain(&self, idx: usize) -> error::Result<u32> {
+        self.bytes.pread_with(self.chain_offset + idx * 4, scroll::LE).map_err(error::Error::from)
+    }
+
+    /// Looks up `name` in the dynamic symbol table `syms`, whose names live in `strtab`.
+    /// Returns the matching symbol's index into `syms`.
+    pub fn find(&self, name: &str, syms: &[Sym], strtab: &Strtab) -> Option<usize> {
+        if self.nbucket == 0 {
+            return None;
+        }
+        let h = Self::hash(name) as usize;
+        let mut i = self.bucket(h % self.nbucket).ok()? as usize;
+        // a malformed/hostile chain array could cycle back on itself; `chain[]` has `nchain`
+        // entries, so a well-formed walk visits at most that many before hitting a 0 terminator
+        for _ in 0..self.nchain {
+            if i == 0 {
+                return None;
+            }
+            if sym_name_matches(i, syms, strtab, name) {
+                return Some(i);
+            }
+            if i >= self.nchain {
+                return None;
+            }
+            i = self.chain(i).ok()? as usize;
+        }
+        None
+    }
+}
+
+/// A GNU `.gnu.hash` section: a Bloom filter guards the bucket/chain walk so a miss can usually
+/// be rejected in O(1) without touching the symbol table at all.
+pub struct GnuHash<'a> {
+    bytes: &'a [u8],
+    nbuckets: usize,
+    symoffset: usize,
+    // number of entries in the chain array, i.e. how many dynamic symbols (from `symoffset`
+    // onward) this hash table actually covers - bounds every chain walk in `find`
+    nchain: usize,
+    bloom_size: usize,
+    bloom_shift: u32,
+    bloom_word_bytes: usize,
+    bloom_offset: usize,
+    buckets_offset: usize,
+    chain_offset: usize,
+}
+
+impl<'a> GnuHash<'a> {
+    /// Parses a `.gnu.hash` section from `bytes`. `nsyms` is the number of entries in the
+    /// associated dynamic symbol table, and `is_64` selects 64- vs 32-bit Bloom filter words
+    /// (the ELF class of the containing object).
+    pub fn parse(bytes: &'a [u8], nsyms: usize, is_64: bool) -> error::Result<Self> {
+        let nbuckets: u32 = bytes.pread_with(0, scroll::LE)?;
+        let symoffset: u32 = bytes.pread_with(4, scroll::LE)?;
+        let bloom_size: u32 = bytes.pread_with(8, scroll::LE)?;
+        let bloom_shift: u32 = bytes.pread_with(12, scroll::LE)?;
+
+        if symoffset as usize > nsyms {
+            return Err(error::Error::Malformed(
+                format!("gnu hash symoffset ({}) is past the end of the {}-entry dynamic symbol table", symoffset, nsyms)
+            ));
+        }
+        let nchain = nsyms - symoffset as usize;
+
+        let bloom_word_bytes = if is_64 { 8 } else { 4 };
+        let bloom_offset = 16;
+        let buckets_offset = bloom_offset + bloom_size as usize * bloom_word_bytes;
+        let chain_offset = buckets_offset + nbuckets as usize * 4;
+
+        Ok(GnuHash {
+            bytes: bytes,
+            nbuckets: nbuckets as usize,
+            symoffset: symoffset as usize,
+            nchain: nchain,
+            bloom_size: bloom_size as usize,
+            bloom_shift: bloom_shift,
+            bloom_word_bytes: bloom_word_bytes,
+            bloom_offset: bloom_offset,
+            buckets_offset: buckets_offset,
+            chain_offset: chain_offset,
+        })
+    }
+
+    /// The GNU `.gnu.hash` string hash (`djb2` with an unsigned accumulator)
+    pub fn hash(name: &str) -> u32 {
+        let mut h: u32 = 5381;
+        for c in name.bytes() {
+            h = h.wrapping_shl(5).wrapping_add(h).wrapping_add(c as u32);
+        }
+        h
+    }
+
+    fn bloom_word(&self, idx: usize) -> error::Result<u64> {
+        let offset = self.bloom_offset + idx * self.bloom_word_bytes;
+        if self.bloom_word_bytes == 8 {
+            self.bytes.pread_with(offset, scroll::LE).map_err(error::Error::from)
+        } else {
+            Ok(self.bytes.pread_with::<u32>(offset, scroll::LE)? as u64)
+        }
+    }
+
+    fn bucket(&self, idx: usize) -> error::Result<u32> {
+        self.bytes.pread_with(self.buckets_offset + idx * 4, scroll::LE).map_err(error::Error::from)
+    }
+
+    fn chain(&self, idx: usize) -> error::Result<u32> {
+        if idx >= self.nchain {
+            return Err(error::Error::Malformed(
+                format!("gnu hash chain index {} out of bounds (nchain: {})", idx, self.nchain)
+            ));
+        }
+        self.bytes.pread_with(self.chain_offset + idx * 4, scroll::LE).map_err(error::Error::from)
+    }
+
+    /// Looks up `name` in the dynamic symbol table `syms`, whose names live in `strtab`.
+    /// Returns the matching symbol's index into `syms`.
+    pub fn find(&self, name: &str, syms: &[Sym], strtab: &Strtab) -> Option<usize> {
+        if self.nbuckets == 0 || self.bloom_size == 0 {
+            return None;
+        }
+        let h = Self::hash(name);
+        let word_bits = (self.bloom_word_bytes * 8) as u32;
+        let word = self.bloom_word((h / word_bits) as usize % self.bloom_size).ok()?;
+        let mask = (1u64 << (h % word_bits)) | (1u64 << ((h >> self.bloom_shift) % word_bits));
+        if word & mask != mask {
+            // the Bloom filter guarantees `name` is absent
+            return None;
+        }
+
+        let mut i = self.bucket(h as usize % self.nbuckets).ok()? as usize;
+        if i == 0 {
+            return None;
+        }
+        loop {
+            if i < self.symoffset {
+                return None;
+            }
+            let chain_word = self.chain(i - self.symoffset).ok()?;
+            if (chain_word | 1) == (h | 1) && sym_name_matches(i, syms, strtab, name) {
+                return Some(i);
+            }
+            if chain_word & 1 != 0 {
+                // low bit set marks the end of this bucket's chain
+                return None;
+            }
+            i += 1;
+        }
+    }
+}
+
+fn sym_name_matches(idx: usize, syms: &[Sym], strtab: &Strtab, name: &str) -> bool {
+    syms.get(idx)
+        .and_then(|sym| strtab.get(sym.st_name as usize).ok())
+        .map_or(false, |sym_name| sym_name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scroll::Pwrite;
+
+    fn sym_at(st_name: u32) -> Sym {
+        Sym { st_name: st_name, ..Sym::default() }
+    }
+
+    // "\0printf\0memmove\0", with index 0 (the empty string) reserved for STN_UNDEF
+    fn strtab_bytes() -> Vec<u8> {
+        b"\0printf\0memmove\0".to_vec()
+    }
+
+    #[test]
+    fn sysv_hash_finds_present_and_rejects_absent() {
+        // one bucket holding a two-symbol chain: dynsym[1] = "printf", dynsym[2] = "memmove"
+        let nbucket = 1u32;
+        let nchain = 3u32; // dynsym[0] (STN_UNDEF), [1], [2]
+        let mut bytes = vec![0u8; 8 + nbucket as usize * 4 + nchain as usize * 4];
+        bytes.pwrite_with(nbucket, 0, scroll::LE).unwrap();
+        bytes.pwrite_with(nchain, 4, scroll::LE).unwrap();
+        bytes.pwrite_with(1u32, 8, scroll::LE).unwrap(); // bucket[0] -> dynsym[1]
+        bytes.pwrite_with(0u32, 12, scroll::LE).unwrap(); // chain[0] (STN_UNDEF, unused)
+        bytes.pwrite_with(2u32, 16, scroll::LE).unwrap(); // chain[1] -> dynsym[2]
+        bytes.pwrite_with(0u32, 20, scroll::LE).unwrap(); // chain[2], terminator
+
+        let strtab_bytes = strtab_bytes();
+        let strtab = Strtab::new(&strtab_bytes, 0x0);
+        let syms = vec![sym_at(0), sym_at(1), sym_at(8)];
+
+        let hash = SysvHash::parse(&bytes).unwrap();
+        assert_eq!(hash.find("printf", &syms, &strtab), Some(1));
+        assert_eq!(hash.find("memmove", &syms, &strtab), Some(2));
+        assert_eq!(hash.find("busta", &syms, &strtab), None);
+    }
+
+    #[test]
+    fn sysv_hash_cyclic_chain_terminates() {
+        // a hostile chain that never hits the 0 terminator: bucket[0] -> 1 -> 2 -> 1 -> 2 -> ...
+        let nbucket = 1u32;
+        let nchain = 3u32;
+        let mut bytes = vec![0u8; 8 + nbucket as usize * 4 + nchain as usize * 4];
+        bytes.pwrite_with(nbucket, 0, scroll::LE).unwrap();
+        bytes.pwrite_with(nchain, 4, scroll::LE).unwrap();
+        bytes.pwrite_with(1u32, 8, scroll::LE).unwrap(); // bucket[0] -> 1
+        bytes.pwrite_with(0u32, 12, scroll::LE).unwrap(); // chain[0], unused
+        bytes.pwrite_with(2u32, 16, scroll::LE).unwrap(); // chain[1] -> 2
+        bytes.pwrite_with(1u32, 20, scroll::LE).unwrap(); // chain[2] -> 1, cycles forever
+
+        let strtab_bytes = strtab_bytes();
+        let strtab = Strtab::new(&strtab_bytes, 0x0);
+        let syms = vec![sym_at(0), sym_at(1), sym_at(8)];
+
+        let hash = SysvHash::parse(&bytes).unwrap();
+        // neither symbol in the cycle is named "busta", so a correct, terminating walk returns None
+        assert_eq!(hash.find("busta", &syms, &strtab), None);
+    }
+
+    // builds a single-bucket `.gnu.hash` section whose chain covers `names`, with the Bloom
+    // filter set to exactly the union of their masks (so only symbols that are, or alias, one of
+    // `names`'s Bloom bits can pass the filter)
+    fn build_gnu_hash(names: &[&str]) -> (Vec<u8>, Vec<Sym>, Vec<u8>) {
+        let bloom_shift = 5u32;
+        let word_bits = 32u32;
+        let symoffset = 1u32;
+
+        let mut strtab_bytes = vec![0u8]; // offset 0: STN_UNDEF's empty name
+        let mut syms = vec![Sym::default()];
+        let mut hashes = Vec::new();
+        for name in names {
+            let offset = strtab_bytes.len() as u32;
+            strtab_bytes.extend_from_slice(name.as_bytes());
+            strtab_bytes.push(0);
+            syms.push(sym_at(offset));
+            hashes.push(GnuHash::hash(name));
+        }
+
+        let mut bloom_word = 0u32;
+        for &h in &hashes {
+            bloom_word |= 1 << (h % word_bits);
+            bloom_word |= 1 << ((h >> bloom_shift) % word_bits);
+        }
+
+        let nbuckets = 1u32;
+        let bloom_size = 1u32;
+        let header_len = 16;
+        let bloom_offset = header_len;
+        let buckets_offset = bloom_offset + bloom_size as usize * 4;
+        let chain_offset = buckets_offset + nbuckets as usize * 4;
+        let mut bytes = vec![0u8; chain_offset + hashes.len() * 4];
+
+        bytes.pwrite_with(nbuckets, 0, scroll::LE).unwrap();
+        bytes.pwrite_with(symoffset, 4, scroll::LE).unwrap();
+        bytes.pwrite_with(bloom_size, 8, scroll::LE).unwrap();
+        bytes.pwrite_with(bloom_shift, 12, scroll::LE).unwrap();
+        bytes.pwrite_with(bloom_word, bloom_offset, scroll::LE).unwrap();
+        bytes.pwrite_with(1u32, buckets_offset, scroll::LE).unwrap(); // bucket[0] -> dynsym[1]
+
+        for (i, &h) in hashes.iter().enumerate() {
+            let is_last = i + 1 == hashes.len();
+            let chain_word = if is_last { h | 1 } else { h & !1 };
+            bytes.pwrite_with(chain_word, chain_offset + i * 4, scroll::LE).unwrap();
+        }
+
+        (bytes, syms, strtab_bytes)
+    }
+
+    #[test]
+    fn gnu_hash_finds_present_symbol() {
+        let (bytes, syms, strtab_bytes) = build_gnu_hash(&["printf"]);
+        let strtab = Strtab::new(&strtab_bytes, 0x0);
+        let hash = GnuHash::parse(&bytes, syms.len(), false).unwrap();
+        assert_eq!(hash.find("printf", &syms, &strtab), Some(1));
+    }
+
+    #[test]
+    fn gnu_hash_rejects_absent_via_bloom() {
+        let (bytes, syms, strtab_bytes) = build_gnu_hash(&["printf"]);
+        let strtab = Strtab::new(&strtab_bytes, 0x0);
+        let hash = GnuHash::parse(&bytes, syms.len(), false).unwrap();
+        // the Bloom filter only encodes "printf"'s bits, so an unrelated name - with overwhelming
+        // likelihood - fails the filter outright, never reaching the bucket/chain walk at all
+        assert_eq!(hash.find("an_absent_symbol_name", &syms, &strtab), None);
+    }
+
+    #[test]
+    fn gnu_hash_chain_longer_than_one() {
+        let (bytes, syms, strtab_bytes) = build_gnu_hash(&["printf", "memmove"]);
+        let strtab = Strtab::new(&strtab_bytes, 0x0);
+        let hash = GnuHash::parse(&bytes, syms.len(), false).unwrap();
+        assert_eq!(hash.find("printf", &syms, &strtab), Some(1));
+        assert_eq!(hash.find("memmove", &syms, &strtab), Some(2));
+    }
+}
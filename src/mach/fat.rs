@@ -0,0 +1,242 @@
+//! Support for "fat", or universal, Mach-o containers: a thin, big-endian header followed by
+//! a table of `fat_arch` records, each describing one thin Mach-o binary embedded later in the
+//! same file - one per supported architecture.
+
+use scroll::Pread;
+use error;
+
+/// Fat magic constant; the fat header and its `fat_arch` table are 32-bit and big-endian
+pub const FAT_MAGIC: u32 = 0xcafebabe;
+pub const FAT_CIGAM: u32 = 0xbebafeca;
+/// Fat magic constant; the fat header and its `fat_arch_64` table are 64-bit and big-endian
+pub const FAT_MAGIC_64: u32 = 0xcafebabf;
+pub const FAT_CIGAM_64: u32 = 0xbfbafeca;
+
+pub const SIZEOF_FAT_HEADER: usize = 8;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pread, Pwrite, SizeWith)]
+/// The header at the very start of a fat/universal Mach-o container
+pub struct FatHeader {
+    /// `FAT_MAGIC`, `FAT_CIGAM`, `FAT_MAGIC_64`, or `FAT_CIGAM_64`
+    pub magic: u32,
+    /// The number of `fat_arch`/`fat_arch_64` records immediately following this header
+    pub nfat_arch: u32,
+}
+
+pub const SIZEOF_FAT_ARCH: usize = 20;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pread, Pwrite, SizeWith)]
+/// One architecture slot of a 32-bit (`FAT_MAGIC`) fat container, as laid out on disk
+pub struct FatArch32 {
+    pub cputype: u32,
+    pub cpusubtype: u32,
+    pub offset: u32,
+    pub size: u32,
+    pub align: u32,
+}
+
+pub const SIZEOF_FAT_ARCH_64: usize = 32;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pread, Pwrite, SizeWith)]
+/// One architecture slot of a 64-bit (`FAT_MAGIC_64`) fat container, as laid out on disk
+pub struct FatArch64 {
+    pub cputype: u32,
+    pub cpusubtype: u32,
+    pub offset: u64,
+    pub size: u64,
+    pub align: u32,
+    pub reserved: u32,
+}
+
+/// A generic, sized-container-agnostic architecture slot: widens the 32-bit record's
+/// `offset`/`size` to `u64` rather than truncating the 64-bit record's, so callers get the same
+/// type regardless of whether the container was `FAT_MAGIC` or `FAT_MAGIC_64`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FatArch {
+    pub cputype: u32,
+    pub cpusubtype: u32,
+    pub offset: u64,
+    pub size: u64,
+    pub align: u32,
+}
+
+impl From<FatArch32> for FatArch {
+    fn from(arch: FatArch32) -> Self {
+        FatArch { cputype: arch.cputype, cpusubtype: arch.cpusubtype, offset: arch.offset as u64, size: arch.size as u64, align: arch.align }
+    }
+}
+
+impl From<FatArch64> for FatArch {
+    fn from(arch: FatArch64) -> Self {
+        FatArch { cputype: arch.cputype, cpusubtype: arch.cpusubtype, offset: arch.offset, size: arch.size, align: arch.align }
+    }
+}
+
+/// A lazy view over a fat/universal Mach-o container's architecture table, letting callers
+/// enumerate the embedded architectures and slice out the thin Mach-o bytes for one of them -
+/// which can then be fed through [`Header`](../header/struct.Header.html)'s normal parse path.
+pub struct MultiArch<'a> {
+    data: &'a [u8],
+    /// The number of architectures in this fat container
+    pub narches: usize,
+    start: usize,
+    is_64: bool,
+}
+
+impl<'a> MultiArch<'a> {
+    /// Parses the fat header (and validates its magic) at the start of `bytes`
+    pub fn parse(bytes: &'a [u8]) -> error::Result<Self> {
+        let magic: u32 = bytes.pread_with(0, scroll::BE)?;
+        let is_64 = match magic {
+            FAT_MAGIC | FAT_CIGAM => false,
+            FAT_MAGIC_64 | FAT_CIGAM_64 => true,
+            _ => return Err(error::Error::BadMagic(magic as u64)),
+        };
+        let header: FatHeader = bytes.pread_with(0, scroll::BE)?;
+        Ok(MultiArch { data: bytes, narches: header.nfat_arch as usize, start: SIZEOF_FAT_HEADER, is_64: is_64 })
+    }
+
+    /// Returns the `idx`th `fat_arch` record
+    pub fn arch(&self, idx: usize) -> error::Result<FatArch> {
+        if idx >= self.narches {
+            return Err(error::Error::Malformed(format!("fat arch index {} out of bounds (narches: {})", idx, self.narches)));
+        }
+        if self.is_64 {
+            let arch: FatArch64 = self.data.pread_with(self.start + idx * SIZEOF_FAT_ARCH_64, scroll::BE)?;
+            Ok(FatArch::from(arch))
+        } else {
+            let arch: FatArch32 = self.data.pread_with(self.start + idx * SIZEOF_FAT_ARCH, scroll::BE)?;
+            Ok(FatArch::from(arch))
+        }
+    }
+
+    /// Iterates over every `fat_arch` record in this container
+    pub fn iter_arches(&self) -> FatArchIter<'a> {
+        FatArchIter { data: self.data, start: self.start, narches: self.narches, is_64: self.is_64, index: 0 }
+    }
+
+    /// Finds the `fat_arch` record matching `(cputype, cpusubtype)`, if any
+    pub fn find(&self, cputype: u32, cpusubtype: u32) -> error::Result<Option<FatArch>> {
+        for arch in self.iter_arches() {
+            let arch = arch?;
+            if arch.cputype == cputype && arch.cpusubtype == cpusubtype {
+                return Ok(Some(arch));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Slices out the embedded thin Mach-o bytes described by `arch`. Errors, rather than
+    /// panicking, if `arch`'s `offset`/`size` don't fit within this container - a malformed or
+    /// hostile fat file shouldn't be able to crash the parser.
+    pub fn slice(&self, arch: &FatArch) -> error::Result<&'a [u8]> {
+        let start = arch.offset as usize;
+        let end = start.checked_add(arch.size as usize)
+            .ok_or_else(|| error::Error::Malformed(format!("fat arch offset+size overflows: {:?}", arch)))?;
+        self.data.get(start..end)
+            .ok_or_else(|| error::Error::Malformed(format!("fat arch offset+size out of bounds (data len: {}): {:?}", self.data.len(), arch)))
+    }
+}
+
+/// Iterates over the `fat_arch` records of a [`MultiArch`](struct.MultiArch.html)
+pub struct FatArchIter<'a> {
+    data: &'a [u8],
+    start: usize,
+    narches: usize,
+    is_64: bool,
+    index: usize,
+}
+
+impl<'a> Iterator for FatArchIter<'a> {
+    type Item = error::Result<FatArch>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.narches {
+            return None;
+        }
+        let arch = if self.is_64 {
+            self.data.pread_with(self.start + self.index * SIZEOF_FAT_ARCH_64, scroll::BE)
+                .map(|arch: FatArch64| FatArch::from(arch))
+                .map_err(error::Error::from)
+        } else {
+            self.data.pread_with(self.start + self.index * SIZEOF_FAT_ARCH, scroll::BE)
+                .map(|arch: FatArch32| FatArch::from(arch))
+                .map_err(error::Error::from)
+        };
+        self.index += 1;
+        Some(arch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scroll::{Pwrite};
+
+    // builds a 32-bit fat container with two thin Mach-o stand-ins embedded at 16-byte aligned
+    // offsets, each just four magic bytes for the purposes of this test
+    fn two_arch_fat() -> Vec<u8> {
+        let thin_magic: u32 = 0xfeedface; // MH_MAGIC
+        let arch0_offset = SIZEOF_FAT_HEADER + 2 * SIZEOF_FAT_ARCH;
+        let arch1_offset = arch0_offset + 16;
+        let mut bytes = vec![0u8; arch1_offset + 16];
+
+        bytes.pwrite_with(FAT_MAGIC, 0, scroll::BE).unwrap();
+        bytes.pwrite_with(2u32, 4, scroll::BE).unwrap();
+
+        let arch0 = FatArch32 { cputype: 7, cpusubtype: 3, offset: arch0_offset as u32, size: 16, align: 4 };
+        let arch1 = FatArch32 { cputype: 0x0100_0007, cpusubtype: 0, offset: arch1_offset as u32, size: 16, align: 4 };
+        bytes.pwrite_with(arch0, SIZEOF_FAT_HEADER, scroll::BE).unwrap();
+        bytes.pwrite_with(arch1, SIZEOF_FAT_HEADER + SIZEOF_FAT_ARCH, scroll::BE).unwrap();
+
+        bytes.pwrite_with(thin_magic, arch0_offset, scroll::LE).unwrap();
+        bytes.pwrite_with(thin_magic, arch1_offset, scroll::LE).unwrap();
+
+        bytes
+    }
+
+    #[test]
+    fn two_arch_fat_both_slices_parse() {
+        let bytes = two_arch_fat();
+        let multi = MultiArch::parse(&bytes).unwrap();
+        assert_eq!(multi.narches, 2);
+
+        let x86 = multi.find(7, 3).unwrap().expect("x86 arch present");
+        let arm64 = multi.find(0x0100_0007, 0).unwrap().expect("arm64 arch present");
+
+        let x86_slice = multi.slice(&x86).unwrap();
+        let arm64_slice = multi.slice(&arm64).unwrap();
+
+        let x86_magic: u32 = x86_slice.pread_with(0, scroll::LE).unwrap();
+        let arm64_magic: u32 = arm64_slice.pread_with(0, scroll::LE).unwrap();
+        assert_eq!(x86_magic, 0xfeedface);
+        assert_eq!(arm64_magic, 0xfeedface);
+    }
+
+    #[test]
+    fn slice_out_of_bounds_errors_instead_of_panicking() {
+        let bytes = two_arch_fat();
+        let multi = MultiArch::parse(&bytes).unwrap();
+        let bogus = FatArch { cputype: 7, cpusubtype: 3, offset: 0, size: bytes.len() as u64 + 1, align: 4 };
+        assert!(multi.slice(&bogus).is_err());
+    }
+
+    #[test]
+    fn fat_arch_64_preserves_offsets_past_4gib() {
+        // a single arch whose offset/size don't fit in a u32, as only a FAT_MAGIC_64 container can express
+        let past_4gib: u64 = 0x1_0000_0100;
+        let arch64 = FatArch64 { cputype: 0x0100_000c, cpusubtype: 0, offset: past_4gib, size: 4, align: 4, reserved: 0 };
+
+        let mut bytes = vec![0u8; SIZEOF_FAT_HEADER + SIZEOF_FAT_ARCH_64];
+        bytes.pwrite_with(FAT_MAGIC_64, 0, scroll::BE).unwrap();
+        bytes.pwrite_with(1u32, 4, scroll::BE).unwrap();
+        bytes.pwrite_with(arch64, SIZEOF_FAT_HEADER, scroll::BE).unwrap();
+
+        let multi = MultiArch::parse(&bytes).unwrap();
+        let arch = multi.arch(0).unwrap();
+        assert_eq!(arch.offset, past_4gib);
+        assert_eq!(arch.size, 4);
+    }
+}
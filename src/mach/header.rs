@@ -1,6 +1,7 @@
 //! A header contains minimal architecture information, the binary kind, the number of load commands, as well as an endianness hint
 
 use std::fmt;
+use std::convert::TryFrom;
 use scroll::{self, ctx};
 use plain::{self, Plain};
 
@@ -8,6 +9,30 @@ use mach::constants::cputype::cpu_type_to_str;
 use error;
 use container::{self, Container};
 
+/// The cpu type of a Mach-o binary, e.g. `CPU_TYPE_X86_64`. A thin wrapper around the raw
+/// `cputype` field which knows how to render itself via [`cpu_type_to_str`](../constants/cputype/fn.cpu_type_to_str.html).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct CpuType(u32);
+
+impl From<u32> for CpuType {
+    fn from(cputype: u32) -> Self {
+        CpuType(cputype)
+    }
+}
+
+impl CpuType {
+    /// The raw `cputype` value this was constructed from
+    pub fn get(&self) -> u32 {
+        self.0
+    }
+}
+
+impl fmt::Display for CpuType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", cpu_type_to_str(self.0))
+    }
+}
+
 // Constants for the flags field of the mach_header
 /// the object file has no undefined references
 pub const MH_NOUNDEFS: u32 = 0x1;
@@ -107,6 +132,83 @@ pub fn flag_to_str(flag: u32) -> &'static str {
     }
 }
 
+// every known MH_* flag, in bit order; used to drive `HeaderFlags::iter`, which names each via `flag_to_str`
+const KNOWN_FLAGS: &'static [u32] = &[
+    MH_NOUNDEFS,
+    MH_INCRLINK,
+    MH_DYLDLINK,
+    MH_BINDATLOAD,
+    MH_PREBOUND,
+    MH_SPLIT_SEGS,
+    MH_LAZY_INIT,
+    MH_TWOLEVEL,
+    MH_FORCE_FLAT,
+    MH_NOMULTIDEFS,
+    MH_NOFIXPREBINDING,
+    MH_PREBINDABLE,
+    MH_ALLMODSBOUND,
+    MH_SUBSECTIONS_VIA_SYMBOLS,
+    MH_CANONICAL,
+    MH_WEAK_DEFINES,
+    MH_BINDS_TO_WEAK,
+    MH_ALLOW_STACK_EXECUTION,
+    MH_ROOT_SAFE,
+    MH_SETUID_SAFE,
+    MH_NO_REEXPORTED_DYLIBS,
+    MH_PIE,
+    MH_DEAD_STRIPPABLE_DYLIB,
+    MH_HAS_TLV_DESCRIPTORS,
+    MH_NO_HEAP_EXECUTION,
+    MH_APP_EXTENSION_SAFE,
+];
+
+/// The `flags` field of a Mach-o header, i.e. a bitmask of `MH_*` constants. Wraps the raw
+/// `u32` so the set flags can be [`iter`](#method.iter)ated instead of hand-rolling bitmask checks.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct HeaderFlags(u32);
+
+impl From<u32> for HeaderFlags {
+    fn from(flags: u32) -> Self {
+        HeaderFlags(flags)
+    }
+}
+
+impl HeaderFlags {
+    /// The raw bitmask this was constructed from
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+    /// Whether every bit of `flag` is set
+    pub fn contains(&self, flag: u32) -> bool {
+        self.0 & flag == flag
+    }
+    /// Iterates over the individual, known `MH_*` flags that are set, along with their names
+    pub fn iter(&self) -> HeaderFlagsIter {
+        HeaderFlagsIter { flags: self.0, index: 0 }
+    }
+}
+
+/// An iterator over the set flags in a [`HeaderFlags`](struct.HeaderFlags.html), yielding
+/// `(flag, name)` pairs. Obtained via [`HeaderFlags::iter`](struct.HeaderFlags.html#method.iter).
+pub struct HeaderFlagsIter {
+    flags: u32,
+    index: usize,
+}
+
+impl Iterator for HeaderFlagsIter {
+    type Item = (u32, &'static str);
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.index < KNOWN_FLAGS.len() {
+            let flag = KNOWN_FLAGS[self.index];
+            self.index += 1;
+            if self.flags & flag == flag {
+                return Some((flag, flag_to_str(flag)));
+            }
+        }
+        None
+    }
+}
+
 /// Mach Header magic constant
 pub const MH_MAGIC: u32 = 0xfeedface;
 pub const MH_CIGAM: u32 = 0xcefaedfe;
@@ -114,6 +216,36 @@ pub const MH_CIGAM: u32 = 0xcefaedfe;
 pub const MH_MAGIC_64: u32 = 0xfeedfacf;
 pub const MH_CIGAM_64: u32 = 0xcffaedfe;
 
+/// The magic number at the start of a Mach-o header, identifying its container width and
+/// endianness (see [`is_little_endian`](#method.is_little_endian) and [`container`](#method.container)).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct Magic(u32);
+
+impl From<u32> for Magic {
+    fn from(magic: u32) -> Self {
+        Magic(magic)
+    }
+}
+
+impl Magic {
+    /// The raw magic value this was constructed from
+    pub fn get(&self) -> u32 {
+        self.0
+    }
+    #[inline]
+    pub fn is_little_endian(&self) -> bool {
+        #[cfg(target_endian = "big")]
+        let res = self.0 == MH_CIGAM || self.0 == MH_CIGAM_64;
+        #[cfg(target_endian = "little")]
+        let res = self.0 == MH_MAGIC || self.0 == MH_MAGIC_64;
+        res
+    }
+    #[inline]
+    pub fn container(&self) -> Container {
+        if self.0 == MH_MAGIC_64 || self.0 == MH_CIGAM_64 { Container::Big } else { Container::Little }
+    }
+}
+
 // Constants for the filetype field of the mach_header
 /// relocatable object file
 pub const MH_OBJECT: u32 = 0x1;
@@ -155,6 +287,62 @@ pub fn filetype_to_str(filetype: u32) -> &'static str {
     }
 }
 
+/// The `filetype` field of a Mach-o header, strongly typed from the raw `MH_*` constant via
+/// [`TryFrom<u32>`](#impl-TryFrom%3Cu32%3E).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Filetype {
+    Object,
+    Execute,
+    Fvmlib,
+    Core,
+    Preload,
+    Dylib,
+    Dylinker,
+    Bundle,
+    DylibStub,
+    Dsym,
+    KextBundle,
+}
+
+impl TryFrom<u32> for Filetype {
+    type Error = error::Error;
+    fn try_from(filetype: u32) -> error::Result<Self> {
+        Ok(match filetype {
+            MH_OBJECT => Filetype::Object,
+            MH_EXECUTE => Filetype::Execute,
+            MH_FVMLIB => Filetype::Fvmlib,
+            MH_CORE => Filetype::Core,
+            MH_PRELOAD => Filetype::Preload,
+            MH_DYLIB => Filetype::Dylib,
+            MH_DYLINKER => Filetype::Dylinker,
+            MH_BUNDLE => Filetype::Bundle,
+            MH_DYLIB_STUB => Filetype::DylibStub,
+            MH_DSYM => Filetype::Dsym,
+            MH_KEXT_BUNDLE => Filetype::KextBundle,
+            _ => return Err(error::Error::Malformed(format!("unknown filetype: 0x{:x}", filetype))),
+        })
+    }
+}
+
+impl fmt::Display for Filetype {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match *self {
+            Filetype::Object => "OBJECT",
+            Filetype::Execute => "EXECUTE",
+            Filetype::Fvmlib => "FVMLIB",
+            Filetype::Core => "CORE",
+            Filetype::Preload => "PRELOAD",
+            Filetype::Dylib => "DYLIB",
+            Filetype::Dylinker => "DYLINKER",
+            Filetype::Bundle => "BUNDLE",
+            Filetype::DylibStub => "DYLIB_STUB",
+            Filetype::Dsym => "DSYM",
+            Filetype::KextBundle => "KEXT_BUNDLE",
+        };
+        write!(f, "{}", s)
+    }
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Default)]
 #[derive(Pread, Pwrite, SizeWith)]
@@ -335,6 +523,26 @@ impl From<Header64> for Header {
 }
 
 impl Header {
+    /// The typed form of [`magic`](#structfield.magic)
+    #[inline]
+    pub fn magic(&self) -> Magic {
+        Magic::from(self.magic)
+    }
+    /// The typed form of [`cputype`](#structfield.cputype)
+    #[inline]
+    pub fn cputype(&self) -> CpuType {
+        CpuType::from(self.cputype)
+    }
+    /// The typed form of [`filetype`](#structfield.filetype); `Err` if it isn't one of the known `MH_*` filetypes
+    #[inline]
+    pub fn filetype(&self) -> error::Result<Filetype> {
+        Filetype::try_from(self.filetype)
+    }
+    /// The typed form of [`flags`](#structfield.flags), iterable over its set `MH_*` bits
+    #[inline]
+    pub fn header_flags(&self) -> HeaderFlags {
+        HeaderFlags::from(self.flags)
+    }
     #[inline]
     pub fn is_little_endian(&self) -> bool {
         #[cfg(target_endian="big")]
@@ -401,6 +609,12 @@ impl<'a> ctx::TryFromCtx<'a, (usize, ctx::DefaultCtx)> for Header {
                         },
                     }
                 },
+                mach::fat::FAT_MAGIC | mach::fat::FAT_CIGAM | mach::fat::FAT_MAGIC_64 | mach::fat::FAT_CIGAM_64 => {
+                    let error = error::Error::Malformed(
+                        format!("bytes are a fat/universal Mach-o container (magic: 0x{:x}); use mach::fat::MultiArch to select an embedded architecture, then parse its thin Mach-o header", magic)
+                    );
+                    Err(error)
+                },
                 _ => {
                     let error = error::Error::BadMagic(magic as u64);
                     Err(error)
@@ -0,0 +1,80 @@
+//! Packed `X.Y.Z` version tags, as used by the `LC_VERSION_MIN_*` and `LC_BUILD_VERSION` load
+//! commands to record a binary's minimum-OS and SDK versions.
+
+use std::fmt;
+use std::str::FromStr;
+use error;
+
+/// A version number packed into a single `u32` as `xxxx.yy.zz`, i.e. `major` in the top 16
+/// bits and `minor`/`release` each in one byte below it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct Version(u32);
+
+impl Version {
+    /// Wraps an already-packed `u32`, as read directly from a version-bearing load command
+    pub fn new(packed: u32) -> Self {
+        Version(packed)
+    }
+    /// The raw, packed `u32` this was constructed from
+    pub fn get(&self) -> u32 {
+        self.0
+    }
+    pub fn major(&self) -> u32 {
+        self.0 >> 16
+    }
+    pub fn minor(&self) -> u32 {
+        (self.0 >> 8) & 0xff
+    }
+    pub fn release(&self) -> u32 {
+        self.0 & 0xff
+    }
+}
+
+impl From<u32> for Version {
+    fn from(packed: u32) -> Self {
+        Version(packed)
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major(), self.minor(), self.release())
+    }
+}
+
+impl FromStr for Version {
+    type Err = error::Error;
+    /// Parses a dotted `"X.Y.Z"` string back into its packed form
+    fn from_str(s: &str) -> error::Result<Self> {
+        let mut parts = s.splitn(3, '.');
+        let invalid = || error::Error::Malformed(format!("invalid version string: {:?}", s));
+        let major: u32 = parts.next().ok_or_else(invalid)?.parse().map_err(|_| invalid())?;
+        let minor: u32 = parts.next().ok_or_else(invalid)?.parse().map_err(|_| invalid())?;
+        let release: u32 = parts.next().ok_or_else(invalid)?.parse().map_err(|_| invalid())?;
+        if major > 0xffff || minor > 0xff || release > 0xff {
+            return Err(invalid());
+        }
+        Ok(Version((major << 16) | (minor << 8) | release))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_major_minor_release() {
+        let version = Version::new(0x000a_0602); // 10.6.2
+        assert_eq!(version.major(), 10);
+        assert_eq!(version.minor(), 6);
+        assert_eq!(version.release(), 2);
+        assert_eq!(version.to_string(), "10.6.2");
+    }
+
+    #[test]
+    fn round_trips_through_display_and_from_str() {
+        let version = Version::new(0x0009_0000); // 9.0.0
+        let parsed: Version = version.to_string().parse().unwrap();
+        assert_eq!(parsed, version);
+    }
+}
@@ -8,6 +8,8 @@ use core::fmt;
 use scroll::{self, ctx, Pread};
 #[cfg(feature = "std")]
 use error;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
 
 /// A common string table format which is indexed by byte offsets (and not
 /// member index). Constructed using [`parse`](#method.parse)
@@ -54,6 +56,34 @@ impl<'a> Strtab<'a> {
     pub fn get(&self, offset: usize) -> scroll::Result<&'a str> {
         get_str(offset, self.bytes, self.delim)
     }
+    /// Lazily iterate over all the strings in the backing bytes, from the start, as `(offset, &str)` pairs.
+    /// This is the `no_std`, allocation-free counterpart to [`to_vec`](#method.to_vec), and reproduces
+    /// its edge-case behavior exactly: a leading delimiter yields an empty string at offset 0, and a
+    /// trailing delimiter does not yield a spurious, final empty entry.
+    pub fn iter(&self) -> StrtabIter<'a> {
+        StrtabIter { bytes: self.bytes, delim: self.delim, offset: 0 }
+    }
+}
+
+/// A lazy iterator over the `(offset, &str)` pairs of a [`Strtab`](struct.Strtab.html), obtained via
+/// [`Strtab::iter`](struct.Strtab.html#method.iter).
+pub struct StrtabIter<'a> {
+    bytes: &'a [u8],
+    delim: ctx::StrCtx,
+    offset: usize,
+}
+
+impl<'a> Iterator for StrtabIter<'a> {
+    type Item = (usize, &'a str);
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.offset >= self.bytes.len() {
+            return None;
+        }
+        let offset = self.offset;
+        let string = get_str(offset, self.bytes, self.delim).ok()?;
+        self.offset += string.len() + 1;
+        Some((offset, string))
+    }
 }
 
 impl<'a> fmt::Debug for Strtab<'a> {
@@ -112,3 +142,138 @@ fn to_vec_newline_delim() {
     assert_eq!(vec.len(), 4);
     assert_eq!(vec, vec!["", "printf", "memmove", "busta"]);
 }
+
+#[test]
+fn iter_matches_to_vec() {
+    let bytes = b"\0printf\0memmove\0busta\0";
+    let strtab = unsafe { Strtab::from_raw(bytes.as_ptr(), bytes.len(), 0x0) };
+    let expected = vec![(0, ""), (1, "printf"), (8, "memmove"), (16, "busta")];
+    let got: Vec<(usize, &str)> = strtab.iter().collect();
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn iter_no_first_null_no_final_null() {
+    let bytes = b"printf\0memmove\0busta";
+    let strtab = unsafe { Strtab::from_raw(bytes.as_ptr(), bytes.len(), 0x0) };
+    let expected = vec![(0, "printf"), (7, "memmove"), (15, "busta")];
+    let got: Vec<(usize, &str)> = strtab.iter().collect();
+    assert_eq!(got, expected);
+}
+
+/// Incrementally builds up a byte-offset string table, deduplicating
+/// identical strings and tail-merging new strings that are a suffix of one
+/// already inserted (e.g. inserting `"printf"` after `"sprintf"` reuses
+/// `"sprintf"`'s bytes, since both end in `"printf\0"`).
+///
+/// Offset `0` is reserved for the leading delimiter, so the empty string is
+/// always available at offset `0` without needing to be inserted.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct StrtabBuilder {
+    // insertion-ordered list of the strings that actually own bytes in the
+    // final table (i.e., excluding strings that were tail-merged into a
+    // longer, previously inserted string)
+    strings: Vec<String>,
+    offsets: HashMap<String, usize>,
+    len: usize,
+}
+
+#[cfg(feature = "std")]
+impl StrtabBuilder {
+    /// Constructs a new, empty `StrtabBuilder`
+    pub fn new() -> Self {
+        // offset 0 is reserved for the table's leading delimiter, which doubles as the empty string
+        StrtabBuilder { strings: Vec::new(), offsets: HashMap::new(), len: 1 }
+    }
+    /// Returns the offset `string` was previously inserted at, if any, without inserting it
+    pub fn get_offset(&self, string: &str) -> Option<usize> {
+        if string.is_empty() {
+            return Some(0);
+        }
+        self.offsets.get(string).cloned()
+    }
+    /// Inserts `string` into the table, returning its stable byte offset.
+    /// Inserting the same string twice returns the same offset; inserting a string that is a
+    /// suffix of an already-inserted string reuses that string's bytes instead of growing the table.
+    pub fn insert(&mut self, string: &str) -> usize {
+        if string.is_empty() {
+            return 0;
+        }
+        if let Some(offset) = self.get_offset(string) {
+            return offset;
+        }
+        if let Some(prev) = self.strings.iter().find(|prev| prev.len() > string.len() && prev.ends_with(string)) {
+            let offset = self.offsets[prev.as_str()] + (prev.len() - string.len());
+            self.offsets.insert(string.to_string(), offset);
+            return offset;
+        }
+        let offset = self.len;
+        self.len += string.len() + 1;
+        self.offsets.insert(string.to_string(), offset);
+        self.strings.push(string.to_string());
+        offset
+    }
+    /// Serializes the table to a byte vector, delimiting every entry (including the leading,
+    /// implicit empty string at offset 0) with `delim`
+    pub fn finalize(self, delim: u8) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.len);
+        bytes.push(delim);
+        for string in &self.strings {
+            bytes.extend_from_slice(string.as_bytes());
+            bytes.push(delim);
+        }
+        bytes
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for StrtabBuilder {
+    fn default() -> Self {
+        // NB: can't derive this - offset 0 is reserved for the leading delimiter, so `len` must
+        // start at 1, not 0
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn strtab_builder_default_matches_new() {
+    let mut builder = StrtabBuilder::default();
+    assert_eq!(builder.insert("printf"), 1);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn strtab_builder_round_trip() {
+    let mut builder = StrtabBuilder::new();
+    let empty_offset = builder.insert("");
+    let printf_offset = builder.insert("printf");
+    let memmove_offset = builder.insert("memmove");
+    let printf_again_offset = builder.insert("printf");
+    let bytes = builder.finalize(0x0);
+
+    assert_eq!(empty_offset, 0);
+    assert_eq!(printf_offset, printf_again_offset);
+
+    let strtab = Strtab::new(&bytes, 0x0);
+    assert_eq!(strtab.get(empty_offset).unwrap(), "");
+    assert_eq!(strtab.get(printf_offset).unwrap(), "printf");
+    assert_eq!(strtab.get(memmove_offset).unwrap(), "memmove");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn strtab_builder_tail_merge() {
+    let mut builder = StrtabBuilder::new();
+    let sprintf_offset = builder.insert("sprintf");
+    let printf_offset = builder.insert("printf");
+    let bytes = builder.finalize(0x0);
+
+    // "printf" is a suffix of "sprintf", so it should be folded into the same bytes
+    assert_eq!(printf_offset, sprintf_offset + 1);
+
+    let strtab = Strtab::new(&bytes, 0x0);
+    assert_eq!(strtab.get(sprintf_offset).unwrap(), "sprintf");
+    assert_eq!(strtab.get(printf_offset).unwrap(), "printf");
+}